@@ -1,15 +1,24 @@
 use bytes::Bytes;
 use egui::TextureId;
-use image::{self, GenericImageView, ImageFormat};
+use image::{self, AnimationDecoder, GenericImageView, ImageFormat};
 use lazy_static::lazy_static;
 use parking_lot::{Mutex, RwLock};
 use std::cell::Cell;
 use std::collections::{hash_map::Entry, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 const TARGET: &str = "Image";
 
+/// Minimum time between `set_progress` repaint signals. A chunked download
+/// can report progress far more often than the UI could ever redraw; without
+/// this, `bytes_stream()` would flood the event loop with one user event per
+/// chunk.
+const PROGRESS_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum Key {
     Https(String),
@@ -18,16 +27,19 @@ pub enum Key {
 #[derive(Clone)]
 pub struct LoadContext(Arc<Inner>);
 
-impl Default for LoadContext {
-    fn default() -> Self {
+impl LoadContext {
+    fn new(repaint_after: crate::RepaintAfter, frame: epi::Frame) -> Self {
         Self(Arc::new(Inner {
             state: RwLock::default(),
             last_access: Cell::new(Instant::now()),
+            size_bytes: Cell::new(0),
+            repaint_after,
+            frame,
+            last_progress_repaint: Cell::new(None),
+            cancelled: AtomicBool::new(false),
         }))
     }
-}
 
-impl LoadContext {
     fn accessed(&self) {
         self.0.last_access.set(Instant::now());
     }
@@ -36,20 +48,110 @@ impl LoadContext {
         self.accessed();
         *self.0.state.write() = LoadingStatus::Error(e.into());
     }
-    fn set_texture_id(&self, id: TextureId) {
+    fn set_texture_id(&self, id: TextureId, size_bytes: u64) {
         self.accessed();
+        self.0.size_bytes.set(size_bytes);
         *self.0.state.write() = LoadingStatus::Loaded(id);
     }
 
+    fn set_animated_textures(&self, frames: Vec<(TextureId, Duration)>, size_bytes: u64) {
+        self.accessed();
+        self.0.size_bytes.set(size_bytes);
+        *self.0.state.write() = LoadingStatus::LoadedAnimated {
+            frames,
+            started: Instant::now(),
+        };
+    }
+
+    /// Records how many bytes of the download have arrived so far and wakes
+    /// the UI so a progress bar can animate.
+    ///
+    /// This fires the real repaint signal (the same one `epi` uses to wake a
+    /// sleeping event loop), not `RepaintAfter`: the latter is only consulted
+    /// lazily on the next redraw, so while the loop sits in `ControlFlow::Wait`
+    /// a `RepaintAfter` deadline alone would never actually wake it up.
+    ///
+    /// The signal itself is throttled to `PROGRESS_REPAINT_INTERVAL`, plus
+    /// always once `received` reaches a known `total`, so a download split
+    /// into many small chunks doesn't fire a user event per chunk. Without a
+    /// known `total` the last chunk is throttled like any other.
+    fn set_progress(&self, received: u64, total: Option<u64>) {
+        self.accessed();
+        *self.0.state.write() = LoadingStatus::Loading { received, total };
+
+        let done = matches!(total, Some(total) if received >= total);
+        let now = Instant::now();
+        let due = match self.0.last_progress_repaint.get() {
+            Some(last) => now.duration_since(last) >= PROGRESS_REPAINT_INTERVAL,
+            None => true,
+        };
+        if done || due {
+            self.0.last_progress_repaint.set(Some(now));
+            self.0.frame.request_repaint();
+        }
+    }
+
+    /// Returns the texture for the current frame, scheduling a repaint for
+    /// when the next frame of an animation becomes due.
     pub fn get_texture_id(&self) -> Option<TextureId> {
         self.accessed();
-        self.0.state.read().as_texture()
+        let (id, next_frame_in) = self.0.state.read().current_texture()?;
+        if let Some(next_frame_in) = next_frame_in {
+            self.0.repaint_after.request_repaint_after(next_frame_in);
+        }
+        Some(id)
     }
 
     pub fn get_error(&self) -> Option<String> {
         self.accessed();
         self.0.state.read().as_error()
     }
+
+    /// Download progress as a `0.0..=1.0` fraction, or `None` if the total
+    /// size isn't known yet (or the load isn't in progress).
+    pub fn get_progress(&self) -> Option<f32> {
+        self.accessed();
+        match &*self.0.state.read() {
+            LoadingStatus::Loading {
+                received,
+                total: Some(total),
+            } => Some(*received as f32 / *total as f32),
+            _ => None,
+        }
+    }
+
+    /// Marks this load as no longer wanted, e.g. because `cleanup` evicted it
+    /// while it was still downloading or decoding. `load_image_async` checks
+    /// this after the network await and bails out before spending CPU on
+    /// decoding bytes nobody will see.
+    fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for LoadContext {
+    /// Explicit drop path: if this is the very last clone of a `LoadContext`
+    /// going away (not `CACHE`'s, not the background task's — those are
+    /// handled by `cleanup` below), nobody can ever call `get_texture_id` or
+    /// `is_cancelled` on it again, so mark it cancelled. This mostly matters
+    /// for a `LoadContext` an app holds onto directly outside of `CACHE`
+    /// (rather than re-fetching it via `get_context` every frame) and then
+    /// drops.
+    ///
+    /// This deliberately does *not* cancel on every drop: `get_context`
+    /// hands out a fresh clone on every call, and the common pattern is to
+    /// drop it at the end of a single `draw()` call, so an unconditional
+    /// cancel-on-drop would mark every in-flight load cancelled after its
+    /// very first frame.
+    fn drop(&mut self) {
+        if Arc::strong_count(&self.0) == 1 {
+            self.0.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
 }
 
 impl std::fmt::Debug for LoadContext {
@@ -65,20 +167,40 @@ impl std::fmt::Debug for LoadContext {
 struct Inner {
     state: RwLock<LoadingStatus>,
     last_access: Cell<Instant>,
+    /// Resident GPU memory (`width * height * 4`, summed over all frames),
+    /// used by `cleanup` to enforce the texture budget.
+    size_bytes: Cell<u64>,
+    repaint_after: crate::RepaintAfter,
+    /// Used by `set_progress` to wake a sleeping event loop immediately.
+    frame: epi::Frame,
+    /// Last time `set_progress` fired the repaint signal; used to throttle it
+    /// to `PROGRESS_REPAINT_INTERVAL`.
+    last_progress_repaint: Cell<Option<Instant>>,
+    /// Set by `cancel()` when `cleanup` evicts this context while its
+    /// background load is still running, and by `LoadContext`'s `Drop` when
+    /// the last clone goes away.
+    cancelled: AtomicBool,
 }
 
 unsafe impl Sync for Inner {}
 
 #[derive(Debug)]
 enum LoadingStatus {
-    Loading,
+    Loading { received: u64, total: Option<u64> },
     Loaded(TextureId),
+    LoadedAnimated {
+        frames: Vec<(TextureId, Duration)>,
+        started: Instant,
+    },
     Error(String),
 }
 
 impl Default for LoadingStatus {
     fn default() -> Self {
-        Self::Loading
+        Self::Loading {
+            received: 0,
+            total: None,
+        }
     }
 }
 
@@ -90,33 +212,88 @@ impl LoadingStatus {
         }
     }
 
-    fn as_texture(&self) -> Option<TextureId> {
+    /// The texture to currently display, plus how long until the next frame
+    /// of an animation is due (if this is an animation).
+    fn current_texture(&self) -> Option<(TextureId, Option<Duration>)> {
         match self {
-            Self::Loaded(id) => Some(*id),
+            Self::Loaded(id) => Some((*id, None)),
+            Self::LoadedAnimated { frames, started } => {
+                current_frame(frames, *started).map(|(id, remaining)| (id, Some(remaining)))
+            }
             _ => None,
         }
     }
+
+    fn all_textures(&self) -> Vec<TextureId> {
+        match self {
+            Self::Loaded(id) => vec![*id],
+            Self::LoadedAnimated { frames, .. } => frames.iter().map(|(id, _)| *id).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Picks the frame that should be visible `elapsed(started)` into the loop,
+/// along with how long it still has left on screen.
+fn current_frame(frames: &[(TextureId, Duration)], started: Instant) -> Option<(TextureId, Duration)> {
+    let total: Duration = frames.iter().map(|(_, delay)| *delay).sum();
+    if frames.is_empty() || total.is_zero() {
+        return frames.first().copied();
+    }
+    let mut offset = Duration::from_nanos((started.elapsed().as_nanos() % total.as_nanos()) as u64);
+    for (id, delay) in frames {
+        if offset < *delay {
+            return Some((*id, *delay - offset));
+        }
+        offset -= *delay;
+    }
+    frames.last().copied()
+}
+
+#[derive(Clone)]
+enum DecodedImage {
+    Still(epi::Image),
+    Animated(Vec<(epi::Image, Duration)>),
 }
 
 #[derive(Clone)]
 pub struct ToUIImage {
     key: Key,
     context: LoadContext,
-    image: epi::Image,
+    image: DecodedImage,
+}
+
+fn image_size_bytes(image: &epi::Image) -> u64 {
+    (image.size[0] * image.size[1] * 4) as u64
 }
 
 impl ToUIImage {
     pub fn finish_load(self, frame: &mut epi::Frame) {
-        let texture = frame.alloc_texture(self.image);
-        log::info!(
-            target: TARGET,
-            "Id is {}",
-            match &texture {
-                TextureId::User(id) => id,
-                _ => unreachable!(),
+        match self.image {
+            DecodedImage::Still(image) => {
+                let size_bytes = image_size_bytes(&image);
+                let texture = frame.alloc_texture(image);
+                log::info!(
+                    target: TARGET,
+                    "Id is {}",
+                    match &texture {
+                        TextureId::User(id) => id,
+                        _ => unreachable!(),
+                    }
+                );
+                self.context.set_texture_id(texture, size_bytes);
+            }
+            DecodedImage::Animated(frames) => {
+                let frame_count = frames.len();
+                let size_bytes: u64 = frames.iter().map(|(image, _)| image_size_bytes(image)).sum();
+                let frames = frames
+                    .into_iter()
+                    .map(|(image, delay)| (frame.alloc_texture(image), delay))
+                    .collect();
+                log::info!(target: TARGET, "Loaded {} animated frames", frame_count);
+                self.context.set_animated_textures(frames, size_bytes);
             }
-        );
-        self.context.set_texture_id(texture);
+        }
     }
 }
 
@@ -129,45 +306,152 @@ impl std::fmt::Debug for ToUIImage {
     }
 }
 
+enum HttpsResult {
+    Fresh {
+        bytes: Bytes,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+    NotModified,
+}
+
 #[cfg(feature = "reqwest")]
-async fn https_get(url: &str) -> Result<(Bytes, Option<ImageFormat>), String> {
-    let response = reqwest::get(url)
+async fn https_get(
+    url: &str,
+    context: &LoadContext,
+    cached: Option<&DiskCacheEntry>,
+) -> Result<HttpsResult, String> {
+    use futures::StreamExt;
+
+    let client = reqwest::Client::new();
+    let mut request = client.get(url);
+    if let Some(cached) = cached {
+        if let Some(etag) = &cached.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let response = request
+        .send()
         .await
         .map_err(|e| format!("Could not connect to server: {:?}", e))?;
-    match response.bytes().await {
-        Ok(bytes) => Ok((bytes, None)),
-        Err(e) => Err(format!("Could not download image: {:?}", e)),
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(HttpsResult::NotModified);
+    }
+
+    let etag = header_value(&response, reqwest::header::ETAG);
+    let last_modified = header_value(&response, reqwest::header::LAST_MODIFIED);
+    let total = response.content_length();
+    let mut received = 0u64;
+    let mut buffer = Vec::with_capacity(total.unwrap_or(0) as usize);
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Could not download image: {:?}", e))?;
+        received += chunk.len() as u64;
+        buffer.extend_from_slice(&chunk);
+        context.set_progress(received, total);
     }
+    Ok(HttpsResult::Fresh {
+        bytes: buffer.into(),
+        etag,
+        last_modified,
+    })
+}
+
+#[cfg(feature = "reqwest")]
+fn header_value(response: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+    response.headers().get(name)?.to_str().ok().map(str::to_owned)
 }
+
 #[cfg(feature = "surf")]
-async fn https_get(url: &str) -> Result<(Bytes, Option<ImageFormat>), String> {
+async fn https_get(
+    url: &str,
+    context: &LoadContext,
+    _cached: Option<&DiskCacheEntry>,
+) -> Result<HttpsResult, String> {
     match surf::get(url).recv_bytes().await {
-        Ok(bytes) => Ok((bytes.into(), None)),
+        Ok(bytes) => {
+            context.set_progress(bytes.len() as u64, Some(bytes.len() as u64));
+            Ok(HttpsResult::Fresh {
+                bytes: bytes.into(),
+                etag: None,
+                last_modified: None,
+            })
+        }
         Err(e) => Err(format!("Could not download image: {:?}", e)),
     }
 }
 
 pub async fn load_image_async(key: Key, context: LoadContext) -> Option<ToUIImage> {
     log::info!(target: TARGET, "Loading {:?}", key);
-    let (bytes, format) = match &key {
-        Key::Https(url) => match https_get(url).await {
-            Ok(res) => res,
+    let cached = load_disk_entry(&key);
+    let bytes = match &key {
+        Key::Https(url) => match https_get(url, &context, cached.as_ref()).await {
+            Ok(HttpsResult::NotModified) => match cached {
+                Some(cached) => {
+                    log::info!(target: TARGET, "{:?} not modified, using disk cache", key);
+                    cached.bytes
+                }
+                None => {
+                    // A 304 only makes sense in response to a conditional request we
+                    // sent, which requires a prior disk cache entry. A server that
+                    // returns 304 anyway (buggy or hostile) must not be trusted.
+                    context.set_error(format!(
+                        "{:?} server returned 304 Not Modified with no disk cache entry to reuse",
+                        key
+                    ));
+                    return None;
+                }
+            },
+            Ok(HttpsResult::Fresh {
+                bytes,
+                etag,
+                last_modified,
+            }) => {
+                save_disk_entry(&key, &bytes, etag.as_deref(), last_modified.as_deref());
+                bytes.to_vec()
+            }
             Err(e) => {
                 context.set_error(e);
                 return None;
             }
         },
     };
-    log::info!(
-        target: TARGET,
-        "Loaded {} bytes, format is {:?}",
-        bytes.len(),
-        format
-    );
-    let result = if let Some(format) = format {
-        image::load_from_memory_with_format(&bytes, format)
-    } else {
-        image::load_from_memory(&bytes)
+
+    if context.is_cancelled() {
+        log::debug!(target: TARGET, "{:?} was cancelled, discarding download", key);
+        return None;
+    }
+
+    log::info!(target: TARGET, "Loaded {} bytes", bytes.len());
+    let format = image::guess_format(&bytes).ok();
+    let image = match format {
+        Some(ImageFormat::Gif) => decode_animated_gif(&bytes, &context),
+        Some(ImageFormat::Png) => decode_png(&bytes, &context),
+        Some(ImageFormat::WebP) => decode_webp(&bytes, &context),
+        _ => decode_still(&bytes, format, &context),
+    }?;
+    Some(ToUIImage {
+        context,
+        key,
+        image,
+    })
+}
+
+fn to_epi_image(image: &image::DynamicImage) -> epi::Image {
+    epi::Image::from_rgba_unmultiplied(
+        [image.width() as usize, image.height() as usize],
+        &image.to_rgba8(),
+    )
+}
+
+fn decode_still(bytes: &[u8], format: Option<ImageFormat>, context: &LoadContext) -> Option<DecodedImage> {
+    let result = match format {
+        Some(format) => image::load_from_memory_with_format(bytes, format),
+        None => image::load_from_memory(bytes),
     };
     match result {
         Ok(image) => {
@@ -177,50 +461,234 @@ pub async fn load_image_async(key: Key, context: LoadContext) -> Option<ToUIImag
                 image.width(),
                 image.height()
             );
-            let image = epi::Image::from_rgba_unmultiplied(
-                [image.width() as usize, image.height() as usize],
-                &image.to_rgba8(),
-            );
-            return Some(ToUIImage {
-                context,
-                key,
-                image,
-            });
+            Some(DecodedImage::Still(to_epi_image(&image)))
         }
         Err(e) => {
             context.set_error(format!("Could not decode image: {:?}", e.to_string()));
+            None
         }
     }
-    None
 }
 
+fn decode_animated_gif(bytes: &[u8], context: &LoadContext) -> Option<DecodedImage> {
+    let decoder = match image::codecs::gif::GifDecoder::new(bytes) {
+        Ok(decoder) => decoder,
+        Err(e) => {
+            context.set_error(format!("Could not decode gif: {:?}", e.to_string()));
+            return None;
+        }
+    };
+    collect_animation_frames("Gif", decoder, context)
+}
+
+fn decode_png(bytes: &[u8], context: &LoadContext) -> Option<DecodedImage> {
+    let mut decoder = match image::codecs::png::PngDecoder::new(bytes) {
+        Ok(decoder) => decoder,
+        Err(e) => {
+            context.set_error(format!("Could not decode png: {:?}", e.to_string()));
+            return None;
+        }
+    };
+    let is_apng = match decoder.is_apng() {
+        Ok(is_apng) => is_apng,
+        Err(e) => {
+            context.set_error(format!("Could not inspect png: {:?}", e.to_string()));
+            return None;
+        }
+    };
+    if !is_apng {
+        return decode_still(bytes, Some(ImageFormat::Png), context);
+    }
+    collect_animation_frames("APNG", decoder.apng(), context)
+}
+
+fn decode_webp(bytes: &[u8], context: &LoadContext) -> Option<DecodedImage> {
+    let decoder = match image::codecs::webp::WebPDecoder::new(bytes) {
+        Ok(decoder) => decoder,
+        Err(e) => {
+            context.set_error(format!("Could not decode webp: {:?}", e.to_string()));
+            return None;
+        }
+    };
+    if !decoder.has_animation() {
+        return decode_still(bytes, Some(ImageFormat::WebP), context);
+    }
+    collect_animation_frames("Animated WebP", decoder, context)
+}
+
+/// Shared tail of the animated decoders: collects every frame of an
+/// `AnimationDecoder`, pairing each with its display `Duration`.
+fn collect_animation_frames<'a>(
+    kind: &str,
+    decoder: impl AnimationDecoder<'a>,
+    context: &LoadContext,
+) -> Option<DecodedImage> {
+    let frames = match decoder.into_frames().collect_frames() {
+        Ok(frames) => frames,
+        Err(e) => {
+            context.set_error(format!("Could not decode {} frames: {:?}", kind, e.to_string()));
+            return None;
+        }
+    };
+    if frames.is_empty() {
+        context.set_error(format!("{} contained no frames", kind));
+        return None;
+    }
+    log::info!(target: TARGET, "{} has {} frames", kind, frames.len());
+    let frames = frames
+        .into_iter()
+        .map(|frame| {
+            let delay: Duration = frame.delay().into();
+            let buffer = frame.into_buffer();
+            let image = epi::Image::from_rgba_unmultiplied(
+                [buffer.width() as usize, buffer.height() as usize],
+                &buffer,
+            );
+            (image, delay)
+        })
+        .collect();
+    Some(DecodedImage::Animated(frames))
+}
+
+const DEFAULT_TEXTURE_BUDGET_BYTES: u64 = 256 * 1024 * 1024;
+
 lazy_static! {
     static ref LAST_CLEANUP_TIME: Mutex<Option<Instant>> = Mutex::default();
     static ref CACHE: Mutex<HashMap<Key, LoadContext>> = Mutex::default();
+    static ref DISK_CACHE_DIR: Mutex<Option<PathBuf>> = Mutex::default();
+    static ref TEXTURE_BUDGET_BYTES: Mutex<u64> = Mutex::new(DEFAULT_TEXTURE_BUDGET_BYTES);
+}
+
+/// Sets the maximum resident texture memory `cleanup` will keep around
+/// before evicting the least-recently-used images. See `App::texture_memory_budget`.
+pub fn set_texture_budget(bytes: u64) {
+    *TEXTURE_BUDGET_BYTES.lock() = bytes;
+}
+
+/// Points the disk-backed image cache at `<app cache dir>/images`, using the
+/// same `app_name` lookup `Persistence`/`FileStorage` use for `<app data
+/// dir>` (just a different `directories_next::ProjectDirs` accessor, since
+/// this is disposable cache data rather than data worth backing up). Call
+/// once from `run` before any images are loaded.
+pub fn init_disk_cache(app_name: &str) {
+    let dir = directories_next::ProjectDirs::from("", "", app_name)
+        .map(|dirs| dirs.cache_dir().join("images"));
+    if let Some(dir) = &dir {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            log::warn!(target: TARGET, "Could not create disk cache dir: {:?}", e);
+            return;
+        }
+    }
+    *DISK_CACHE_DIR.lock() = dir;
+}
+
+struct DiskCacheEntry {
+    bytes: Vec<u8>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+fn disk_cache_paths(dir: &std::path::Path, key: &Key) -> (PathBuf, PathBuf) {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    key.hash(&mut hasher);
+    let hash = format!("{:016x}", hasher.finish());
+    (dir.join(format!("{}.bin", hash)), dir.join(format!("{}.meta", hash)))
+}
+
+fn load_disk_entry(key: &Key) -> Option<DiskCacheEntry> {
+    let dir = DISK_CACHE_DIR.lock().clone()?;
+    let (bin_path, meta_path) = disk_cache_paths(&dir, key);
+    let bytes = std::fs::read(bin_path).ok()?;
+    let meta = std::fs::read_to_string(meta_path).unwrap_or_default();
+    let mut lines = meta.lines();
+    let etag = lines.next().filter(|s| !s.is_empty()).map(str::to_owned);
+    let last_modified = lines.next().filter(|s| !s.is_empty()).map(str::to_owned);
+    Some(DiskCacheEntry {
+        bytes,
+        etag,
+        last_modified,
+    })
 }
 
-pub fn get_context<BG: crate::Background>(bg: &BG, key: Key) -> LoadContext {
+fn save_disk_entry(key: &Key, bytes: &[u8], etag: Option<&str>, last_modified: Option<&str>) {
+    let Some(dir) = DISK_CACHE_DIR.lock().clone() else {
+        return;
+    };
+    let (bin_path, meta_path) = disk_cache_paths(&dir, key);
+    if let Err(e) = std::fs::write(&bin_path, bytes) {
+        log::warn!(target: TARGET, "Could not write disk cache entry: {:?}", e);
+        return;
+    }
+    let meta = format!("{}\n{}\n", etag.unwrap_or_default(), last_modified.unwrap_or_default());
+    if let Err(e) = std::fs::write(&meta_path, meta) {
+        log::warn!(target: TARGET, "Could not write disk cache metadata: {:?}", e);
+    }
+}
+
+pub fn get_context<BG: crate::Background>(
+    bg: &BG,
+    key: Key,
+    repaint_after: &crate::RepaintAfter,
+    frame: &epi::Frame,
+) -> LoadContext {
     let mut lock = CACHE.lock();
     match lock.entry(key) {
         Entry::Occupied(o) => o.get().clone(),
         Entry::Vacant(v) => {
-            let context = LoadContext::default();
+            let context = LoadContext::new(repaint_after.clone(), frame.clone());
             bg.start_loading_image(v.key().clone(), context.clone());
             v.insert(context).clone()
         }
     }
 }
 
+/// Evicts least-recently-used images until resident texture memory is back
+/// under the configured budget. A still-downloading entry that's gone
+/// stale (see the `Loading` branch below) is cancelled and dropped outright
+/// rather than left to finish; a loaded entry still referenced elsewhere (an
+/// `Arc`-shared `LoadContext` held by more than just `CACHE`) is left alone,
+/// same as the old TTL-based cleanup did.
 pub fn cleanup(frame: &epi::Frame) {
-    let mut keys_to_remove = Vec::new();
+    let budget = *TEXTURE_BUDGET_BYTES.lock();
     let mut write = CACHE.lock();
-    for (key, ctx) in write.iter_mut() {
-        if ctx.0.last_access.get().elapsed() > Duration::from_secs(60) {
-            keys_to_remove.push(key.clone());
-        }
+
+    let mut total_bytes: u64 = write.values().map(|ctx| ctx.0.size_bytes.get()).sum();
+    if total_bytes <= budget {
+        return;
     }
 
-    for key in keys_to_remove {
+    let mut keys_by_age: Vec<Key> = write.keys().cloned().collect();
+    keys_by_age.sort_by_key(|key| write[key].0.last_access.get());
+
+    for key in keys_by_age {
+        if total_bytes <= budget {
+            break;
+        }
+
+        // A load still in progress holds no texture memory yet, so evicting
+        // it wouldn't get us any closer to budget by itself — but it's the
+        // least-recently-accessed entry we have, i.e. nothing drew it this
+        // tick, so it's the "scrolled off screen while still downloading"
+        // case this cancellation exists for. `CACHE`'s own clone plus the
+        // `load_image_async` task's owned `context` param means a genuinely
+        // in-flight load always has a strong count of (at least) 2; only
+        // skip cancelling when something *beyond* those two still holds a
+        // clone (an app deliberately keeping the `LoadContext` around for
+        // longer than one frame), since that's a real signal the load is
+        // still wanted.
+        if matches!(&*write[&key].0.state.read(), LoadingStatus::Loading { .. }) {
+            if Arc::strong_count(&write[&key].0) <= 2 {
+                write[&key].cancel();
+                write.remove(&key);
+            }
+            continue;
+        }
+
+        let size_bytes = write[&key].0.size_bytes.get();
+        if Arc::strong_count(&write[&key].0) > 1 {
+            continue;
+        }
+
         let val = write.remove(&key).unwrap();
         let inner = match Arc::try_unwrap(val.0) {
             Ok(inner) => inner,
@@ -232,10 +700,10 @@ pub fn cleanup(frame: &epi::Frame) {
                 continue;
             }
         };
-        let read = inner.state.read();
-        if let Some(id) = read.as_texture() {
-            log::debug!(target: TARGET, "Cleaning up {:?}", id);
+        for id in inner.state.into_inner().all_textures() {
+            log::debug!(target: TARGET, "Evicting {:?}", id);
             frame.free_texture(id);
         }
+        total_bytes -= size_bytes;
     }
 }
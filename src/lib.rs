@@ -6,6 +6,7 @@ pub use self::traits::*;
 pub mod winit {
     pub use glium::glutin::event::VirtualKeyCode;
     pub use glium::glutin::event_loop::EventLoopProxy;
+    pub use glium::glutin::window::WindowId;
 }
 
 use egui_glium::egui_winit::WindowSettings;
@@ -13,10 +14,11 @@ use epi::{file_storage::FileStorage, Storage};
 use glium::glutin::{
     self,
     event::{ElementState, Event, WindowEvent},
-    event_loop::{ControlFlow, EventLoop},
-    window::Window,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    window::{Window, WindowId},
 };
 use std::{
+    collections::HashMap,
     marker::PhantomData,
     sync::Arc,
     time::{Duration, Instant},
@@ -37,136 +39,307 @@ impl<T: RepaintSignalMessage> epi::backend::RepaintSignal for RepaintSignal<T> {
     }
 }
 
+/// A shared "wake me up at this instant" deadline, so code that only needs to
+/// repaint occasionally (a spinner, a fade-in) doesn't have to spin the event
+/// loop with `ControlFlow::Poll`.
+#[derive(Clone)]
+pub struct RepaintAfter(Arc<std::sync::Mutex<Option<Instant>>>);
+
+impl Default for RepaintAfter {
+    fn default() -> Self {
+        Self(Arc::new(std::sync::Mutex::new(None)))
+    }
+}
+
+impl RepaintAfter {
+    /// Ask for a repaint no later than `duration` from now. If an earlier
+    /// deadline is already pending, it is kept.
+    pub fn request_repaint_after(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        let mut lock = self.0.lock().unwrap();
+        *lock = Some(match *lock {
+            Some(existing) if existing < deadline => existing,
+            _ => deadline,
+        });
+    }
+
+    fn next_wake(&self) -> Option<Instant> {
+        *self.0.lock().unwrap()
+    }
+
+    /// Clears the deadline if it has passed, returning whether it did.
+    fn consume_if_elapsed(&self) -> bool {
+        let mut lock = self.0.lock().unwrap();
+        if let Some(deadline) = *lock {
+            if deadline <= Instant::now() {
+                *lock = None;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Describes a secondary window an app wants opened, returned from
+/// `App::open_window_requested`. `key` is the persistence key its window
+/// geometry is saved under, so give each logical window a stable one. `key`
+/// is not deduplicated against already-open windows, so see the caveat on
+/// `App::open_window_requested` before returning one.
+pub struct WindowSpec {
+    pub key: &'static str,
+    pub title: &'static str,
+}
+
 pub fn run<T: App>(app: T) {
     let title = app.title();
     let mut persistence = Persistence::from_app_name(title);
+    image::init_disk_cache(title);
+    image::set_texture_budget(app.texture_memory_budget());
     let event_loop = EventLoop::with_user_event();
 
     let background = app.spawn_background(event_loop.create_proxy());
-    let display = create_display(&persistence, &event_loop, title);
 
-    let repaint_signal = RepaintSignal {
+    let repaint_signal: Arc<dyn epi::backend::RepaintSignal> = Arc::new(RepaintSignal {
         proxy: std::sync::Mutex::new(event_loop.create_proxy()),
         pd: PhantomData,
-    };
+    });
 
-    let mut integration = Integration::new(
+    const MAIN_WINDOW_KEY: &str = "window";
+    let main_display = create_display(
+        persistence.load_window_settings(MAIN_WINDOW_KEY).as_ref(),
+        &event_loop,
         title,
-        egui_glium::EguiGlium::new(&display),
-        app,
-        Arc::new(repaint_signal),
-        background,
+        None,
+    );
+    let main_window_id = main_display.gl_window().window().id();
+
+    let mut windows = HashMap::new();
+    windows.insert(
+        main_window_id,
+        WindowState::new(
+            main_display,
+            title,
+            MAIN_WINDOW_KEY,
+            repaint_signal.clone(),
+        ),
     );
 
+    let mut integration = Integration::new(app, background);
     let mut last_image_cleanup = Instant::now();
 
-    event_loop.run(move |event, _, control_flow| {
-        let mut redraw = || {
-            if last_image_cleanup.elapsed().as_secs() >= 1 {
-                image::cleanup(&integration.frame);
-                last_image_cleanup = Instant::now();
-            }
-            let (needs_repaint, mut tex_allocation_data, shapes) =
-                integration.update(display.gl_window().window());
-            let clipped_meshes = integration.egui_glium.egui_ctx.tessellate(shapes);
-
-            let painter = &mut integration.egui_glium.painter;
-
-            for (id, image) in tex_allocation_data.creations {
-                painter.set_texture(&display, id, &image);
+    event_loop.run(move |event, event_loop_target, control_flow| {
+        match event {
+            // Platform-dependent event handlers to workaround a winit bug
+            // See: https://github.com/rust-windowing/winit/issues/987
+            // See: https://github.com/rust-windowing/winit/issues/1619
+            Event::RedrawEventsCleared if cfg!(windows) => {
+                let window_ids: Vec<WindowId> = windows.keys().copied().collect();
+                for window_id in window_ids {
+                    redraw_window(
+                        &mut windows,
+                        &mut integration,
+                        main_window_id,
+                        window_id,
+                        control_flow,
+                    );
+                }
             }
-            {
-                use glium::Surface as _;
-                let mut target = display.draw();
-                let color: f32 = 3.0 / 255.0;
-                target.clear_color(color, color, color, 1.0);
-
-                painter.paint_meshes(
-                    &display,
-                    &mut target,
-                    integration.egui_glium.egui_ctx.pixels_per_point(),
-                    clipped_meshes,
-                    &integration.egui_glium.egui_ctx.font_image(),
+            Event::RedrawRequested(window_id) if !cfg!(windows) => {
+                redraw_window(
+                    &mut windows,
+                    &mut integration,
+                    main_window_id,
+                    window_id,
+                    control_flow,
                 );
-
-                target.finish().unwrap();
             }
 
-            for id in tex_allocation_data.destructions.drain(..) {
-                log::info!(target: "image", "Destroying texture {}", id);
-                painter.free_texture(id);
+            // A scheduled `request_repaint_after` deadline was reached: wake up and redraw.
+            Event::NewEvents(glutin::event::StartCause::ResumeTimeReached { .. }) => {
+                for window in windows.values() {
+                    window.display.gl_window().window().request_redraw();
+                }
             }
 
-            *control_flow = if !integration.app.is_running() {
-                ControlFlow::Exit
-            } else if needs_repaint {
-                display.gl_window().window().request_redraw();
-                ControlFlow::Poll
-            } else {
-                ControlFlow::Wait
-            };
-        };
-
-        match event {
-            // Platform-dependent event handlers to workaround a winit bug
-            // See: https://github.com/rust-windowing/winit/issues/987
-            // See: https://github.com/rust-windowing/winit/issues/1619
-            Event::RedrawEventsCleared if cfg!(windows) => redraw(),
-            Event::RedrawRequested(_) if !cfg!(windows) => redraw(),
-
-            Event::WindowEvent { event, .. } => {
+            Event::WindowEvent { window_id, event } => {
                 if matches!(event, WindowEvent::CloseRequested | WindowEvent::Destroyed) {
-                    *control_flow = glutin::event_loop::ControlFlow::Exit;
+                    if window_id == main_window_id {
+                        *control_flow = ControlFlow::Exit;
+                    } else {
+                        windows.remove(&window_id);
+                    }
                 }
 
-                let egui_consumed = integration.egui_glium.on_event(&event);
-                if !egui_consumed {
-                    match event {
-                        WindowEvent::KeyboardInput { input, .. } => {
-                            match (input.virtual_keycode, input.state) {
-                                (Some(virtual_keycode), ElementState::Pressed) => integration
-                                    .app
-                                    .key_pressed(&mut integration.background, virtual_keycode),
-                                (Some(virtual_keycode), ElementState::Released) => integration
-                                    .app
-                                    .key_released(&mut integration.background, virtual_keycode),
-                                _ => {}
+                if let Some(window_state) = windows.get_mut(&window_id) {
+                    let egui_consumed = window_state.egui_glium.on_event(&event);
+                    if !egui_consumed {
+                        match event {
+                            WindowEvent::KeyboardInput { input, .. } => {
+                                match (input.virtual_keycode, input.state) {
+                                    (Some(virtual_keycode), ElementState::Pressed) => integration
+                                        .app
+                                        .key_pressed(&mut integration.background, virtual_keycode),
+                                    (Some(virtual_keycode), ElementState::Released) => integration
+                                        .app
+                                        .key_released(&mut integration.background, virtual_keycode),
+                                    _ => {}
+                                }
                             }
+                            e => log::trace!(target: "Event", "Unhandled {:?}", e),
                         }
-                        e => log::trace!(target: "Event", "Unhandled {:?}", e),
                     }
-                }
 
-                display.gl_window().window().request_redraw();
+                    window_state.display.gl_window().window().request_redraw();
+                }
             }
 
             glutin::event::Event::UserEvent(e) if e.is_repaint_signal() => {
-                display.gl_window().window().request_redraw();
+                for window in windows.values() {
+                    window.display.gl_window().window().request_redraw();
+                }
             }
             glutin::event::Event::UserEvent(msg) => {
                 if let Some(img) = msg.is_image_loaded_response() {
-                    img.finish_load(&mut integration.frame);
+                    if let Some(main_window) = windows.get_mut(&main_window_id) {
+                        // `alloc_texture` mints a `TextureId` whose `user` half
+                        // is only ever registered with *this* frame's own
+                        // `egui_glium::Painter` (via `tex_allocation_data` in
+                        // `redraw_window`). Shared GL lists make the
+                        // underlying GL texture object resolvable from any
+                        // window's context, but that registration step is
+                        // per-painter, so a secondary window currently can't
+                        // render a background-loaded image — see the caveat
+                        // on `WindowState` below. Background images are only
+                        // ever uploaded here, to the main window.
+                        img.finish_load(&mut main_window.frame);
+                    }
                 } else {
                     integration
                         .app
                         .handle_message(&mut integration.background, msg);
-                    display.gl_window().window().request_redraw();
+                }
+                for window in windows.values() {
+                    window.display.gl_window().window().request_redraw();
                 }
             }
 
             _ => (),
         }
-        persistence.maybe_autosave(&display);
+
+        if last_image_cleanup.elapsed().as_secs() >= 1 {
+            if let Some(main_window) = windows.get(&main_window_id) {
+                image::cleanup(&main_window.frame);
+            }
+            last_image_cleanup = Instant::now();
+        }
+
+        if let Some(spec) = integration.app.open_window_requested() {
+            if let Some(main_window) = windows.get(&main_window_id) {
+                let window_settings = persistence.load_window_settings(spec.key);
+                let new_display = create_display(
+                    window_settings.as_ref(),
+                    event_loop_target,
+                    spec.title,
+                    Some(&main_window.display),
+                );
+                let new_window_id = new_display.gl_window().window().id();
+                windows.insert(
+                    new_window_id,
+                    WindowState::new(new_display, spec.title, spec.key, repaint_signal.clone()),
+                );
+            }
+        }
+
+        persistence.maybe_autosave_all(
+            windows
+                .values()
+                .map(|w| (w.persistence_key, w.display.gl_window().window())),
+        );
     });
 }
 
+/// Resolves a single `RedrawRequested`/`RedrawEventsCleared` to the
+/// matching window, draws it, and (only for the main window) derives the
+/// next `ControlFlow`.
+fn redraw_window<APP: App>(
+    windows: &mut HashMap<WindowId, WindowState>,
+    integration: &mut Integration<APP>,
+    main_window_id: WindowId,
+    window_id: WindowId,
+    control_flow: &mut ControlFlow,
+) {
+    let is_main = window_id == main_window_id;
+    let window_state = match windows.get_mut(&window_id) {
+        Some(window_state) => window_state,
+        None => return,
+    };
+
+    let (needs_repaint, mut tex_allocation_data, shapes) = window_state.update(
+        window_id,
+        is_main,
+        &mut integration.app,
+        &mut integration.background,
+        &integration.repaint_after,
+    );
+    let clipped_meshes = window_state.egui_glium.egui_ctx.tessellate(shapes);
+
+    let painter = &mut window_state.egui_glium.painter;
+
+    for (id, image) in tex_allocation_data.creations {
+        painter.set_texture(&window_state.display, id, &image);
+    }
+    {
+        use glium::Surface as _;
+        let mut target = window_state.display.draw();
+        let color: f32 = 3.0 / 255.0;
+        target.clear_color(color, color, color, 1.0);
+
+        painter.paint_meshes(
+            &window_state.display,
+            &mut target,
+            window_state.egui_glium.egui_ctx.pixels_per_point(),
+            clipped_meshes,
+            &window_state.egui_glium.egui_ctx.font_image(),
+        );
+
+        target.finish().unwrap();
+    }
+
+    for id in tex_allocation_data.destructions.drain(..) {
+        log::info!(target: "image", "Destroying texture {}", id);
+        painter.free_texture(id);
+    }
+
+    if !is_main {
+        if needs_repaint {
+            window_state.display.gl_window().window().request_redraw();
+        }
+        return;
+    }
+
+    // Only the main window drives `control_flow`, so only consume the
+    // deadline here: a secondary window redrawing must not be able to clear
+    // a pending `WaitUntil` wake that belongs to the main window.
+    let deadline_elapsed = integration.repaint_after.consume_if_elapsed();
+
+    *control_flow = if !integration.app.is_running() {
+        ControlFlow::Exit
+    } else if needs_repaint || deadline_elapsed {
+        window_state.display.gl_window().window().request_redraw();
+        ControlFlow::Poll
+    } else if let Some(deadline) = integration.repaint_after.next_wake() {
+        ControlFlow::WaitUntil(deadline)
+    } else {
+        ControlFlow::Wait
+    };
+}
+
 pub struct Persistence {
     storage: Option<FileStorage>,
     last_auto_save: std::time::Instant,
 }
 
 impl Persistence {
-    const WINDOW_KEY: &'static str = "window";
     const AUTO_SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
     pub fn from_app_name(app_name: &str) -> Self {
@@ -176,27 +349,25 @@ impl Persistence {
         }
     }
 
-    pub fn save(&mut self, display: &glium::Display) {
+    pub fn save_window(&mut self, key: &str, window: &Window) {
         if let Some(storage) = &mut self.storage {
-            epi::set_value(
-                storage,
-                Self::WINDOW_KEY,
-                &WindowSettings::from_display(display.gl_window().window()),
-            );
+            epi::set_value(storage, key, &WindowSettings::from_display(window));
             storage.flush();
         }
     }
 
-    pub fn maybe_autosave(&mut self, display: &glium::Display) {
+    pub fn maybe_autosave_all<'w>(&mut self, windows: impl Iterator<Item = (&'static str, &'w Window)>) {
         let now = std::time::Instant::now();
         if now - self.last_auto_save > Self::AUTO_SAVE_INTERVAL {
-            self.save(display);
+            for (key, window) in windows {
+                self.save_window(key, window);
+            }
             self.last_auto_save = now;
         }
     }
 
-    pub fn load_window_settings(&self) -> Option<crate::WindowSettings> {
-        epi::get_value(self.storage.as_ref()?, Self::WINDOW_KEY)
+    pub fn load_window_settings(&self, key: &str) -> Option<crate::WindowSettings> {
+        epi::get_value(self.storage.as_ref()?, key)
     }
 }
 
@@ -204,23 +375,50 @@ pub struct Context<'a, BG> {
     pub ctx: &'a egui::CtxRef,
     pub frame: &'a epi::Frame,
     pub background: &'a mut BG,
+    pub repaint_after: &'a RepaintAfter,
 }
 
 pub struct Integration<APP: App> {
-    frame: epi::Frame,
     background: <APP as App>::Background,
-    pub egui_glium: egui_glium::EguiGlium,
+    repaint_after: RepaintAfter,
     pub app: APP,
 }
 
 impl<APP: App> Integration<APP> {
+    fn new(app: APP, background: <APP as App>::Background) -> Self {
+        Self {
+            app,
+            background,
+            repaint_after: RepaintAfter::default(),
+        }
+    }
+}
+
+/// The UI state for a single OS window: its GL context, egui integration and
+/// `epi::Frame`. Shares GL lists with the main window's context (see
+/// `create_display`), so the underlying GL texture *objects* are resolvable
+/// from any window's context — but `egui_glium::Painter` keeps its own
+/// `TextureId -> texture` table per window, populated only by that window's
+/// own `set_texture` calls in `redraw_window`. Sharing the GL context does
+/// *not* share that table: a `TextureId` allocated via one window's
+/// `epi::Frame` (e.g. a background-loaded image, see the `UserEvent` handler
+/// in `run`) is only ever renderable in the window whose painter it was
+/// registered with.
+struct WindowState {
+    display: glium::Display,
+    egui_glium: egui_glium::EguiGlium,
+    frame: epi::Frame,
+    persistence_key: &'static str,
+}
+
+impl WindowState {
     fn new(
+        display: glium::Display,
         title: &'static str,
-        egui_glium: egui_glium::EguiGlium,
-        app: APP,
+        persistence_key: &'static str,
         repaint_signal: Arc<dyn epi::backend::RepaintSignal>,
-        background: <APP as App>::Background,
     ) -> Self {
+        let egui_glium = egui_glium::EguiGlium::new(&display);
         let frame = epi::Frame::new(epi::backend::FrameData {
             info: epi::IntegrationInfo {
                 name: title,
@@ -233,16 +431,20 @@ impl<APP: App> Integration<APP> {
             repaint_signal,
         });
         Self {
-            frame,
+            display,
             egui_glium,
-            app,
-            background,
+            frame,
+            persistence_key,
         }
     }
 
-    pub fn update(
+    fn update<APP: App>(
         &mut self,
-        window: &Window,
+        window_id: WindowId,
+        is_main: bool,
+        app: &mut APP,
+        background: &mut APP::Background,
+        repaint_after: &RepaintAfter,
     ) -> (
         bool,
         epi::backend::TexAllocationData,
@@ -250,23 +452,35 @@ impl<APP: App> Integration<APP> {
     ) {
         let frame_start = std::time::Instant::now();
 
-        let raw_input = self.egui_glium.egui_winit.take_egui_input(window);
+        let raw_input = self
+            .egui_glium
+            .egui_winit
+            .take_egui_input(self.display.gl_window().window());
+        let frame = &mut self.frame;
         let (egui_output, shapes) = self.egui_glium.egui_ctx.run(raw_input, |egui_ctx| {
-            self.app.draw(&mut Context {
+            let mut context = Context {
                 ctx: egui_ctx,
-                frame: &mut self.frame,
-                background: &mut self.background,
-            });
+                frame,
+                background,
+                repaint_after,
+            };
+            if is_main {
+                app.draw(&mut context);
+            } else {
+                app.draw_window(window_id, &mut context);
+            }
         });
 
         let needs_repaint = egui_output.needs_repaint;
-        self.egui_glium
-            .egui_winit
-            .handle_output(window, &self.egui_glium.egui_ctx, egui_output);
+        self.egui_glium.egui_winit.handle_output(
+            self.display.gl_window().window(),
+            &self.egui_glium.egui_ctx,
+            egui_output,
+        );
 
         let app_output = self.frame.take_app_output();
         let tex_allocation_data = egui_glium::egui_winit::epi::handle_app_output(
-            window,
+            self.display.gl_window().window(),
             self.egui_glium.egui_ctx.pixels_per_point(),
             app_output,
         );
@@ -279,24 +493,27 @@ impl<APP: App> Integration<APP> {
 }
 
 fn create_display<MSG>(
-    persistence: &Persistence,
-    event_loop: &glutin::event_loop::EventLoop<MSG>,
+    window_settings: Option<&crate::WindowSettings>,
+    event_loop: &EventLoopWindowTarget<MSG>,
     title: &str,
+    shared_with: Option<&glium::Display>,
 ) -> glium::Display {
-    let window_settings = persistence.load_window_settings();
     let window_builder = egui_glium::egui_winit::epi::window_builder(
         &epi::NativeOptions {
             maximized: true,
             ..Default::default()
         },
-        &window_settings,
+        &window_settings.cloned(),
     )
     .with_title(title);
-    let context_builder = glutin::ContextBuilder::new()
+    let mut context_builder = glutin::ContextBuilder::new()
         .with_depth_buffer(0)
         .with_srgb(true)
         .with_stencil_buffer(0)
         .with_vsync(true);
+    if let Some(shared_with) = shared_with {
+        context_builder = context_builder.with_shared_lists(&*shared_with.gl_window());
+    }
 
     glium::Display::new(window_builder, context_builder, event_loop).unwrap()
 }
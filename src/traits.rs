@@ -16,9 +16,45 @@ pub trait App: 'static {
 
     fn title(&self) -> &'static str;
     fn is_running(&self) -> bool;
+
+    /// Maximum resident texture memory, in bytes, before the least-recently-used
+    /// images are evicted. Defaults to 256 MiB; override to match your hardware.
+    fn texture_memory_budget(&self) -> u64 {
+        256 * 1024 * 1024
+    }
     fn spawn_background(&self, proxy: EventLoopProxy<Self::Msg>) -> Self::Background;
     fn handle_message(&mut self, bg: &mut Self::Background, msg: Self::Msg);
     fn key_pressed(&mut self, bg: &mut Self::Background, key: VirtualKeyCode);
     fn key_released(&mut self, bg: &mut Self::Background, key: VirtualKeyCode);
     fn draw(&mut self, context: &mut crate::Context<Self::Background>);
+
+    /// Polled once at the end of every event-loop iteration (i.e. far more
+    /// often than once per redrawn frame — many events can fire between
+    /// frames). Return `Some(spec)` to open a new secondary window; it will
+    /// then receive `draw_window` calls of its own. Defaults to never opening
+    /// any, so existing single-window apps are unaffected.
+    ///
+    /// Nothing de-duplicates by `spec.key`: returning `Some` again before the
+    /// window is actually opened, or on every subsequent poll because the
+    /// "open" request was never cleared, opens a new OS window each time.
+    /// Clear whatever flag triggered the request (e.g. a button press) before
+    /// returning `Some`, and don't set it again while a window for that `key`
+    /// is still open.
+    fn open_window_requested(&mut self) -> Option<crate::WindowSpec> {
+        None
+    }
+
+    /// Draws the contents of a secondary window previously opened via
+    /// `open_window_requested`. Defaults to a no-op.
+    ///
+    /// Background-loaded images (`crate::image`) currently only render in
+    /// the main window: their `TextureId` is only ever registered with the
+    /// main window's `egui_glium::Painter` (see `WindowState`'s doc comment
+    /// in `lib.rs`), so `get_texture_id` results used here won't display.
+    fn draw_window(
+        &mut self,
+        _window_id: crate::winit::WindowId,
+        _context: &mut crate::Context<Self::Background>,
+    ) {
+    }
 }